@@ -14,8 +14,9 @@ use gfx::display_list::{BorderDetails, BorderRadii, ClipScrollNode};
 use gfx::display_list::{ClipScrollNodeIndex, ClipScrollNodeType, ClippingRegion, DisplayItem};
 use gfx::display_list::{DisplayList, StackingContextType};
 use msg::constellation_msg::PipelineId;
+use style::cursor::Cursor;
 use webrender_api::{self, ClipAndScrollInfo, ClipId, ClipMode, ComplexClipRegion};
-use webrender_api::{DisplayListBuilder, LayoutTransform};
+use webrender_api::{DisplayListBuilder, LayoutTransform, PropertyBinding};
 
 pub trait WebRenderDisplayListConverter {
     fn convert_to_webrender(&self, pipeline_id: PipelineId) -> DisplayListBuilder;
@@ -23,6 +24,7 @@ pub trait WebRenderDisplayListConverter {
 
 trait WebRenderDisplayItemConverter {
     fn prim_info(&self) -> webrender_api::LayoutPrimitiveInfo;
+    fn is_disjoint_with_local_clip(&self) -> bool;
     fn convert_to_webrender(
         &self,
         builder: &mut DisplayListBuilder,
@@ -76,19 +78,22 @@ impl WebRenderDisplayListConverter for DisplayList {
 
 impl WebRenderDisplayItemConverter for DisplayItem {
     fn prim_info(&self) -> webrender_api::LayoutPrimitiveInfo {
-        let tag = match self.base().metadata.pointing {
-            Some(cursor) => Some((self.base().metadata.node.0 as u64, cursor)),
-            None => None,
-        };
         webrender_api::LayoutPrimitiveInfo {
             rect: self.base().bounds.to_layout(),
             local_clip: self.base().local_clip,
-            // TODO(gw): Make use of the WR backface visibility functionality.
-            is_backface_visible: true,
-            tag,
+            is_backface_visible: self.base().metadata.backface_visibility,
+            // See `DisplayItem::HitTest` below.
+            tag: None,
         }
     }
 
+    fn is_disjoint_with_local_clip(&self) -> bool {
+        let bounds = self.base().bounds.to_layout();
+        // Use the clip's bounding rect, since `LocalClip` may be a rounded rect.
+        let clip_rect = self.base().local_clip.clip_rect();
+        bounds.intersection(clip_rect).is_none()
+    }
+
     fn convert_to_webrender(
         &self,
         builder: &mut DisplayListBuilder,
@@ -116,10 +121,30 @@ impl WebRenderDisplayItemConverter for DisplayItem {
             *current_clip_and_scroll_info = clip_and_scroll_info;
         }
 
+        let is_primitive = match *self {
+            DisplayItem::SolidColor(_) | DisplayItem::Text(_) | DisplayItem::Image(_) |
+            DisplayItem::YuvImage(_) | DisplayItem::Border(_) | DisplayItem::Gradient(_) |
+            DisplayItem::RadialGradient(_) | DisplayItem::Line(_) | DisplayItem::BoxShadow(_) |
+            DisplayItem::HitTest(_) => true,
+            _ => false,
+        };
+        if is_primitive && self.is_disjoint_with_local_clip() {
+            // Bounds and local clip never overlap; this primitive would never paint.
+            return;
+        }
+
         match *self {
             DisplayItem::SolidColor(ref item) => {
                 builder.push_rect(&self.prim_info(), item.color);
             },
+            DisplayItem::HitTest(ref item) => {
+                // Colorless rect: paints nothing, just carries the hit-test tag. `pointing` is
+                // only set for elements with a custom `cursor`; fall back to the platform default.
+                let cursor = item.metadata.pointing.unwrap_or(Cursor::Default);
+                let mut info = self.prim_info();
+                info.tag = Some((item.metadata.node.0 as u64, cursor));
+                builder.push_rect(&info, webrender_api::ColorF::TRANSPARENT);
+            },
             DisplayItem::Text(ref item) => {
                 let mut origin = item.baseline_origin.clone();
                 let mut glyphs = vec![];
@@ -172,6 +197,16 @@ impl WebRenderDisplayItemConverter for DisplayItem {
                     }
                 }
             },
+            DisplayItem::YuvImage(ref item) => {
+                if item.stretch_size.width > 0.0 && item.stretch_size.height > 0.0 {
+                    builder.push_yuv_image(
+                        &self.prim_info(),
+                        item.yuv_data,
+                        item.color_space,
+                        item.image_rendering,
+                    );
+                }
+            },
             DisplayItem::Border(ref item) => {
                 let details = match item.details {
                     BorderDetails::Normal(ref border) => {
@@ -271,24 +306,61 @@ impl WebRenderDisplayItemConverter for DisplayItem {
                 let stacking_context = &item.stacking_context;
                 debug_assert!(stacking_context.context_type == StackingContextType::Real);
 
-                let transform = stacking_context
-                    .transform
-                    .map(|transform| LayoutTransform::from_untyped(&transform).into());
-                let perspective = stacking_context
-                    .perspective
-                    .map(|perspective| LayoutTransform::from_untyped(&perspective));
-
+                // Same source as `prim_info()`'s `is_backface_visible`, so a stacking context and
+                // the primitives it contains never disagree on backface visibility.
+                let stacking_context_info = webrender_api::LayoutPrimitiveInfo {
+                    is_backface_visible: self.base().metadata.backface_visibility,
+                    ..webrender_api::LayoutPrimitiveInfo::new(stacking_context.bounds.to_layout())
+                };
                 builder.push_stacking_context(
-                    &webrender_api::LayoutPrimitiveInfo::new(stacking_context.bounds.to_layout()),
+                    &stacking_context_info,
                     stacking_context.scroll_policy,
-                    transform,
                     stacking_context.transform_style,
-                    perspective,
                     stacking_context.mix_blend_mode,
                     stacking_context.filters.clone(),
+                    stacking_context.glyph_raster_space,
                 );
             },
             DisplayItem::PopStackingContext(_) => builder.pop_stacking_context(),
+            DisplayItem::PushReferenceFrame(ref item) => {
+                // `perspective` establishes its own (static) reference frame, nested outside the
+                // transform's, matching how `transform-style: preserve-3d` expects perspective to
+                // apply before the element's own transform.
+                if let Some(perspective) = item.perspective {
+                    let perspective_clip_id = builder.push_reference_frame(
+                        item.bounds.to_layout(),
+                        Some(PropertyBinding::Value(LayoutTransform::from_untyped(
+                            &perspective,
+                        ))),
+                        item.origin.to_layout(),
+                    );
+                    builder.push_clip_id(perspective_clip_id);
+                }
+
+                // Carried as a `PropertyBinding` so a compositor-driven animation of this
+                // transform can update it without regenerating the display list.
+                let transform = item.transform.map(|transform| match transform {
+                    PropertyBinding::Value(transform) => {
+                        PropertyBinding::Value(LayoutTransform::from_untyped(&transform))
+                    },
+                    PropertyBinding::Binding(key, transform) => {
+                        PropertyBinding::Binding(key, LayoutTransform::from_untyped(&transform))
+                    },
+                });
+
+                let clip_id = builder.push_reference_frame(
+                    item.bounds.to_layout(),
+                    transform,
+                    item.origin.to_layout(),
+                );
+                builder.push_clip_id(clip_id);
+            },
+            DisplayItem::PopReferenceFrame(ref item) => {
+                builder.pop_clip_id();
+                if item.has_perspective {
+                    builder.pop_clip_id();
+                }
+            },
             DisplayItem::DefineClipScrollNode(ref item) => {
                 let node = &clip_scroll_nodes[item.node_index.0];
                 let parent_id = get_id(clip_ids, node.parent_index);
@@ -312,6 +384,14 @@ impl WebRenderDisplayItemConverter for DisplayItem {
                             None,
                             scroll_sensitivity,
                         ),
+                    ClipScrollNodeType::ClipChain(ref clip_chain_indices) => {
+                        // Resolves to one ClipId referencing all of the given parents.
+                        let clip_ids_in_chain: Vec<ClipId> = clip_chain_indices
+                            .iter()
+                            .map(|index| get_id(clip_ids, *index))
+                            .collect();
+                        ClipId::ClipChain(builder.define_clip_chain(None, clip_ids_in_chain))
+                    },
                     ClipScrollNodeType::StickyFrame(ref sticky_data) => {
                         // TODO: Add define_sticky_frame_with_parent to WebRender.
                         builder.push_clip_id(parent_id);